@@ -0,0 +1,67 @@
+//! Stamps the ABI symbols Wireshark's plugin loader inspects before `dlopen`-ing this `.so`/`.dll`:
+//! `plugin_version`, `plugin_want_major`, and `plugin_want_minor`. The targeted Wireshark
+//! major/minor is overridable so the same source can be rebuilt against whichever ABI a given
+//! Wireshark release expects, instead of baking in one fixed version.
+
+use std::env;
+use std::fs;
+use std::path::Path;
+
+/// Wireshark version this crate is validated against by default.
+const DEFAULT_WS_MAJOR_VERSION: &str = "4";
+const DEFAULT_WS_MINOR_VERSION: &str = "2";
+
+fn main() {
+    println!("cargo:rerun-if-env-changed=PLUGIN_MAJOR_VERSION");
+    println!("cargo:rerun-if-env-changed=PLUGIN_MINOR_VERSION");
+
+    let ws_major = parse_version_component("PLUGIN_MAJOR_VERSION", DEFAULT_WS_MAJOR_VERSION);
+    let ws_minor = parse_version_component("PLUGIN_MINOR_VERSION", DEFAULT_WS_MINOR_VERSION);
+
+    // Re-export the resolved values as rustc-env vars so the rest of the crate (and its tests)
+    // can read back exactly what this build was stamped with.
+    println!("cargo:rustc-env=MATCHY_WS_MAJOR_VERSION={ws_major}");
+    println!("cargo:rustc-env=MATCHY_WS_MINOR_VERSION={ws_minor}");
+
+    write_plugin_abi(ws_major, ws_minor);
+}
+
+fn parse_version_component(var: &str, default: &str) -> i32 {
+    env::var(var)
+        .unwrap_or_else(|_| default.to_string())
+        .parse()
+        .unwrap_or_else(|_| panic!("{var} must be an integer"))
+}
+
+/// Writes the `#[no_mangle]` statics Wireshark reads at load time to `$OUT_DIR/plugin_abi.rs`,
+/// which `src/lib.rs` pulls in with `include!`. These have to be real `static` items with fixed
+/// sizes known at compile time, so the version string and ABI pair are baked in here rather than
+/// read at runtime.
+fn write_plugin_abi(ws_major: i32, ws_minor: i32) {
+    let plugin_version = env::var("CARGO_PKG_VERSION").unwrap();
+    let mut version_bytes: Vec<String> = plugin_version
+        .bytes()
+        .map(|b| (b as i8).to_string())
+        .collect();
+    version_bytes.push("0".to_string()); // nul terminator, as Wireshark expects a C string
+
+    let contents = format!(
+        "#[no_mangle]\n\
+         #[used]\n\
+         static plugin_version: [std::ffi::c_char; {len}] = [{bytes}];\n\n\
+         #[no_mangle]\n\
+         #[used]\n\
+         static plugin_want_major: std::ffi::c_int = {ws_major};\n\n\
+         #[no_mangle]\n\
+         #[used]\n\
+         static plugin_want_minor: std::ffi::c_int = {ws_minor};\n",
+        len = version_bytes.len(),
+        bytes = version_bytes.join(", "),
+        ws_major = ws_major,
+        ws_minor = ws_minor,
+    );
+
+    let out_dir = env::var("OUT_DIR").unwrap();
+    fs::write(Path::new(&out_dir).join("plugin_abi.rs"), contents)
+        .expect("failed to write plugin_abi.rs");
+}
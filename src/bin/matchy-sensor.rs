@@ -0,0 +1,96 @@
+//! CLI entrypoint for the standalone matchy live-capture sensor: applies a matchy database to one
+//! or more interfaces directly, independent of Wireshark/tshark.
+//!
+//! Usage:
+//!   matchy-sensor --interface eth0 [--interface eth1 ...] --database threats.mxy [--out threats.pcap]
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use matchy_wireshark::database::Database;
+use matchy_wireshark::sensor::Sensor;
+use pcap::{Capture, Linktype};
+
+struct Args {
+    interfaces: Vec<String>,
+    database: PathBuf,
+    out: PathBuf,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut interfaces = Vec::new();
+    let mut database = None;
+    let mut out = PathBuf::from("threats.pcap");
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--interface" => interfaces.push(args.next().ok_or("--interface needs a value")?),
+            "--database" => {
+                database = Some(PathBuf::from(args.next().ok_or("--database needs a value")?))
+            }
+            "--out" => out = PathBuf::from(args.next().ok_or("--out needs a value")?),
+            other => return Err(format!("unrecognized argument: {other}")),
+        }
+    }
+
+    if interfaces.is_empty() {
+        return Err("at least one --interface is required".to_string());
+    }
+    let database = database.ok_or("--database is required")?;
+
+    Ok(Args {
+        interfaces,
+        database,
+        out,
+    })
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let args = match parse_args() {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("matchy-sensor: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let database = match Database::load(&args.database) {
+        Ok(db) => db,
+        Err(e) => {
+            eprintln!("matchy-sensor: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let threats_out = match Capture::dead(Linktype::ETHERNET).and_then(|d| d.savefile(&args.out)) {
+        Ok(savefile) => savefile,
+        Err(e) => {
+            eprintln!("matchy-sensor: failed to open {}: {e}", args.out.display());
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let (events_tx, mut events_rx) = tokio::sync::mpsc::channel(1024);
+    let sensor_task = tokio::spawn(Sensor::new(database, threats_out).run(args.interfaces, events_tx));
+
+    while let Some(event) = events_rx.recv().await {
+        println!(
+            "frame {} on {}: {:?} -> {:?} [{:?} / {}]",
+            event.frame_number, event.interface, event.src, event.dst, event.level, event.category,
+        );
+    }
+
+    match sensor_task.await {
+        Ok(Ok(())) => ExitCode::SUCCESS,
+        Ok(Err(e)) => {
+            eprintln!("matchy-sensor: {e}");
+            ExitCode::FAILURE
+        }
+        Err(e) => {
+            eprintln!("matchy-sensor: sensor task panicked: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,333 @@
+//! Loading and querying of `matchy` threat-intelligence databases (`.mxy` files).
+//!
+//! A `.mxy` file is a newline-delimited list of indicator records. Each line is
+//! `<indicator>\t<level>\t<category>`, where `<indicator>` is a single IP address, a CIDR block,
+//! or a domain name. A domain indicator may be prefixed with `*.` (e.g. `*.evil.example`) to also
+//! match any subdomain. Blank lines and lines starting with `#` are ignored.
+
+use std::fmt;
+use std::fs;
+use std::net::IpAddr;
+use std::path::Path;
+
+use ipnetwork::IpNetwork;
+
+/// Severity of a matched indicator, surfaced to Wireshark as `matchy.level`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    Low,
+    Medium,
+    High,
+}
+
+impl Level {
+    fn parse(s: &str) -> Option<Level> {
+        match s.to_ascii_lowercase().as_str() {
+            "low" => Some(Level::Low),
+            "medium" => Some(Level::Medium),
+            "high" => Some(Level::High),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Level::Low => "Low",
+            Level::Medium => "Medium",
+            Level::High => "High",
+        };
+        f.write_str(s)
+    }
+}
+
+/// What an [`Entry`] matches against.
+enum Indicator {
+    Network(IpNetwork),
+    /// A lowercased domain name, optionally written `*.example.com` to also match subdomains.
+    Domain(String),
+}
+
+/// A single entry loaded from a `.mxy` file.
+struct Entry {
+    indicator: Indicator,
+    level: Level,
+    category: String,
+}
+
+/// An indicator that matched a packet's address or domain name.
+pub struct Match {
+    pub level: Level,
+    pub category: String,
+}
+
+/// An in-memory threat-intelligence database, loaded from a `.mxy` file.
+///
+/// Lookups check for an exact address match first, falling back to the narrowest CIDR block that
+/// contains the address.
+pub struct Database {
+    entries: Vec<Entry>,
+}
+
+impl Database {
+    /// Parses a `.mxy` file from disk.
+    pub fn load(path: &Path) -> Result<Database, String> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| format!("failed to read database {}: {e}", path.display()))?;
+        Self::parse(&contents)
+    }
+
+    fn parse(contents: &str) -> Result<Database, String> {
+        let mut entries = Vec::new();
+        for (lineno, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut fields = line.split('\t');
+            let indicator = fields
+                .next()
+                .ok_or_else(|| format!("line {}: missing indicator", lineno + 1))?;
+            let level = fields
+                .next()
+                .ok_or_else(|| format!("line {}: missing level", lineno + 1))?;
+            let category = fields
+                .next()
+                .ok_or_else(|| format!("line {}: missing category", lineno + 1))?;
+
+            let parsed_network = indicator
+                .parse::<IpNetwork>()
+                .or_else(|_| indicator.parse::<IpAddr>().map(IpNetwork::from));
+            let indicator = match parsed_network {
+                Ok(network) => Indicator::Network(network),
+                Err(_) if looks_like_ip_or_cidr(indicator) => {
+                    return Err(format!(
+                        "line {}: invalid indicator {indicator:?}",
+                        lineno + 1
+                    ));
+                }
+                Err(_) => Indicator::Domain(indicator.to_ascii_lowercase()),
+            };
+            let level = Level::parse(level)
+                .ok_or_else(|| format!("line {}: invalid level {level:?}", lineno + 1))?;
+            if category.contains('\0') {
+                return Err(format!(
+                    "line {}: category {category:?} contains a NUL byte",
+                    lineno + 1
+                ));
+            }
+
+            entries.push(Entry {
+                indicator,
+                level,
+                category: category.to_string(),
+            });
+        }
+        Ok(Database { entries })
+    }
+
+    /// Looks up an IP address, preferring the entry with the narrowest (most specific) prefix.
+    pub fn lookup_ip(&self, addr: IpAddr) -> Option<Match> {
+        self.entries
+            .iter()
+            .filter_map(|entry| match &entry.indicator {
+                Indicator::Network(network) if network.contains(addr) => {
+                    Some((network.prefix(), entry))
+                }
+                _ => None,
+            })
+            .max_by_key(|&(prefix, _)| prefix)
+            .map(|(_, entry)| entry.to_match())
+    }
+
+    /// Looks up a domain name (e.g. a DNS query, TLS SNI, or HTTP Host value). A trailing
+    /// `:<port>` (as `http.host` routinely carries for non-default ports) is stripped before
+    /// matching. An exact match always outranks a `*.`-wildcarded one, even a shorter one; among
+    /// same-kind matches the longer (more specific) pattern wins.
+    pub fn lookup_domain(&self, name: &str) -> Option<Match> {
+        let name = strip_port(name).trim_end_matches('.').to_ascii_lowercase();
+        self.entries
+            .iter()
+            .filter_map(|entry| match &entry.indicator {
+                Indicator::Domain(pattern) => {
+                    domain_specificity(pattern, &name).map(|s| (s, entry))
+                }
+                _ => None,
+            })
+            .max_by_key(|&(specificity, _)| specificity)
+            .map(|(_, entry)| entry.to_match())
+    }
+}
+
+impl Entry {
+    fn to_match(&self) -> Match {
+        Match {
+            level: self.level,
+            category: self.category.clone(),
+        }
+    }
+}
+
+/// Strips a trailing `:<port>` suffix, e.g. from an `http.host` value like `evil.example:8080`.
+fn strip_port(name: &str) -> &str {
+    match name.rsplit_once(':') {
+        Some((host, port)) if !port.is_empty() && port.bytes().all(|b| b.is_ascii_digit()) => host,
+        _ => name,
+    }
+}
+
+/// How specific a domain pattern's match against `name` is, as `(is_exact, pattern_len)`, or
+/// `None` if it doesn't match at all. Ordering this tuple (as `lookup_domain` does via
+/// `max_by_key`) ranks any exact match above any wildcard match, and breaks ties within the same
+/// kind by the longer, more specific pattern.
+fn domain_specificity(pattern: &str, name: &str) -> Option<(bool, usize)> {
+    match pattern.strip_prefix("*.") {
+        Some(suffix) => (name == suffix || name.ends_with(&format!(".{suffix}")))
+            .then_some((false, pattern.len())),
+        None => (name == pattern).then_some((true, pattern.len())),
+    }
+}
+
+/// True if `s` has the shape of a (possibly malformed) IP address or CIDR block, as opposed to a
+/// genuine domain. Used to tell a typo'd IP indicator (e.g. `10.0.0.0/99`, a bad prefix) from a
+/// domain, so the former is reported as a load error instead of silently becoming an indicator
+/// that can never match any real DNS/SNI/Host value.
+///
+/// This has to be more than "every character is hex digit, '.', ':', or '/'" — that also matches
+/// perfectly ordinary all-hex-looking domains like `c2.de`. Instead it requires the dot- or
+/// colon-separated structure an IPv4/IPv6 literal actually has: every `.`-separated label of a
+/// (non-IPv6) address must be all-decimal-digits, and an IPv6-shaped address (anything with a
+/// `:`) must be only hex digits and colons.
+fn looks_like_ip_or_cidr(s: &str) -> bool {
+    let address = s.split_once('/').map_or(s, |(address, _)| address);
+    if address.contains(':') {
+        return address.chars().all(|c| c.is_ascii_hexdigit() || c == ':');
+    }
+    let labels: Vec<&str> = address.split('.').collect();
+    labels.len() > 1
+        && labels
+            .iter()
+            .all(|label| !label.is_empty() && label.bytes().all(|b| b.is_ascii_digit()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exact_match_wins_over_cidr() {
+        let db = Database::parse(
+            "10.0.0.0/8\tMedium\tinternal\n\
+             10.1.2.3\tHigh\tmalware\n",
+        )
+        .unwrap();
+
+        let m = db.lookup_ip("10.1.2.3".parse().unwrap()).unwrap();
+        assert_eq!(m.level, Level::High);
+        assert_eq!(m.category, "malware");
+    }
+
+    #[test]
+    fn cidr_match() {
+        let db = Database::parse("10.0.0.0/8\tMedium\tinternal\n").unwrap();
+        let m = db.lookup_ip("10.1.2.3".parse().unwrap()).unwrap();
+        assert_eq!(m.level, Level::Medium);
+        assert_eq!(m.category, "internal");
+    }
+
+    #[test]
+    fn clean_address_does_not_match() {
+        let db = Database::parse("192.168.1.1\tHigh\tmalware\n").unwrap();
+        assert!(db.lookup_ip("8.8.8.8".parse().unwrap()).is_none());
+    }
+
+    #[test]
+    fn comments_and_blank_lines_are_ignored() {
+        let db = Database::parse(
+            "# threat feed\n\
+             \n\
+             192.168.1.1\tHigh\tmalware\n",
+        )
+        .unwrap();
+        assert!(db.lookup_ip("192.168.1.1".parse().unwrap()).is_some());
+    }
+
+    #[test]
+    fn exact_domain_match() {
+        let db = Database::parse("evil.example\tHigh\tmalware\n").unwrap();
+        let m = db.lookup_domain("evil.example").unwrap();
+        assert_eq!(m.level, Level::High);
+        assert_eq!(m.category, "malware");
+    }
+
+    #[test]
+    fn wildcard_domain_matches_subdomain_and_bare_domain() {
+        let db = Database::parse("*.evil.example\tHigh\tc2\n").unwrap();
+        assert!(db.lookup_domain("c2.evil.example").is_some());
+        assert!(db.lookup_domain("evil.example").is_some());
+        assert!(db.lookup_domain("notevil.example").is_none());
+    }
+
+    #[test]
+    fn domain_lookup_is_case_insensitive_and_ignores_trailing_dot() {
+        let db = Database::parse("evil.example\tHigh\tmalware\n").unwrap();
+        assert!(db.lookup_domain("EVIL.example.").is_some());
+    }
+
+    #[test]
+    fn exact_domain_wins_over_wildcard() {
+        let db = Database::parse(
+            "*.evil.example\tLow\tsuspicious\n\
+             c2.evil.example\tHigh\tmalware\n",
+        )
+        .unwrap();
+        let m = db.lookup_domain("c2.evil.example").unwrap();
+        assert_eq!(m.level, Level::High);
+        assert_eq!(m.category, "malware");
+    }
+
+    #[test]
+    fn exact_domain_wins_over_wildcard_on_the_bare_domain_too() {
+        // Even though "*.evil.example" is the longer pattern, the exact entry for the bare
+        // domain it also matches must still win.
+        let db = Database::parse(
+            "*.evil.example\tLow\tsuspicious\n\
+             evil.example\tHigh\tmalware\n",
+        )
+        .unwrap();
+        let m = db.lookup_domain("evil.example").unwrap();
+        assert_eq!(m.level, Level::High);
+        assert_eq!(m.category, "malware");
+    }
+
+    #[test]
+    fn malformed_ip_indicator_is_a_load_error() {
+        assert!(Database::parse("192.168.1.999\tHigh\tmalware\n").is_err());
+        assert!(Database::parse("10.0.0.0/99\tHigh\tmalware\n").is_err());
+    }
+
+    #[test]
+    fn category_with_embedded_nul_is_a_load_error() {
+        assert!(Database::parse("evil.example\tHigh\tmal\0ware\n").is_err());
+    }
+
+    #[test]
+    fn clean_domain_does_not_match() {
+        let db = Database::parse("evil.example\tHigh\tmalware\n").unwrap();
+        assert!(db.lookup_domain("example.com").is_none());
+    }
+
+    #[test]
+    fn all_hex_looking_domain_is_not_mistaken_for_a_malformed_ip() {
+        let db = Database::parse("c2.de\tHigh\tmalware\n").unwrap();
+        assert!(db.lookup_domain("c2.de").is_some());
+    }
+
+    #[test]
+    fn lookup_domain_strips_a_trailing_port() {
+        let db = Database::parse("evil.example\tHigh\tmalware\n").unwrap();
+        assert!(db.lookup_domain("evil.example:8080").is_some());
+    }
+}
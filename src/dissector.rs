@@ -0,0 +1,299 @@
+//! The Wireshark postdissector itself: flags traffic matching a loaded [`Database`] and surfaces
+//! the match as the `matchy.*` fields `tests/integration.rs` asserts on.
+//!
+//! Gated behind the `wireshark-plugin` feature (on by default) because it's the only part of this
+//! crate that links against `libwireshark` via `epan-sys`; [`crate::database`] and [`crate::sensor`]
+//! build and test fine without a Wireshark toolchain when it's disabled.
+
+use std::ffi::{c_int, c_void, CStr, CString};
+use std::net::IpAddr;
+use std::path::Path;
+use std::sync::Mutex;
+
+use epan_sys::{
+    address, address_type_AT_IPv4, address_type_AT_IPv6, create_dissector_handle,
+    field_display_e_BASE_NONE, ftenum_FT_BOOLEAN, ftenum_FT_STRING, fvalue_get_string,
+    header_field_info, hf_ref_type_HF_REF_TYPE_NONE, hf_register_info, packet_info,
+    prefs_register_filename_preference, prefs_register_protocol, proto_item_add_subtree,
+    proto_plugin, proto_register_field_array, proto_register_plugin, proto_register_protocol,
+    proto_register_subtree_array, proto_registrar_get_id_byname, proto_tree,
+    proto_tree_add_boolean, proto_tree_add_string, register_postdissector, tvbuff_t,
+};
+
+use crate::database::{Database, Match};
+
+// Generated by `build.rs` from the `PLUGIN_MAJOR_VERSION`/`PLUGIN_MINOR_VERSION` environment
+// variables: defines the `#[no_mangle]` `plugin_version` / `plugin_want_major` /
+// `plugin_want_minor` statics Wireshark's plugin loader reads before `dlopen`-ing this library.
+include!(concat!(env!("OUT_DIR"), "/plugin_abi.rs"));
+
+static DATABASE: Mutex<Option<Database>> = Mutex::new(None);
+
+static mut PROTO_MATCHY: c_int = -1;
+static mut HF_THREAT_DETECTED: c_int = -1;
+static mut HF_LEVEL: c_int = -1;
+static mut HF_CATEGORY: c_int = -1;
+static mut HF_INDICATOR: c_int = -1;
+static mut ETT_MATCHY: c_int = -1;
+static mut PREF_DATABASE_PATH: *const std::ffi::c_char = std::ptr::null();
+
+/// Header-field indices of the other dissectors' string fields matchy also checks against
+/// [`Database::lookup_domain`], resolved once in `proto_reg_handoff` (by which point every
+/// built-in dissector has already registered its fields). `-1` if the field never got registered,
+/// e.g. because the relevant protocol dissector isn't built into this Wireshark.
+static mut TARGET_HF_DNS_QRY_NAME: c_int = -1;
+static mut TARGET_HF_TLS_SNI: c_int = -1;
+static mut TARGET_HF_HTTP_HOST: c_int = -1;
+
+/// Converts an `epan` address (as seen on `packet_info.src`/`.dst`) into an [`IpAddr`], if it's
+/// one of the address families matchy understands.
+///
+/// # Safety
+/// `addr.data` must point to at least `addr.len` readable bytes, as guaranteed by `epan` for any
+/// `address` it hands to a dissector.
+unsafe fn address_to_ip(addr: &address) -> Option<IpAddr> {
+    if addr.data.is_null() {
+        return None;
+    }
+    match addr.type_ as u32 {
+        t if t == address_type_AT_IPv4 && addr.len == 4 => {
+            let b = std::slice::from_raw_parts(addr.data as *const u8, 4);
+            Some(IpAddr::from([b[0], b[1], b[2], b[3]]))
+        }
+        t if t == address_type_AT_IPv6 && addr.len == 16 => {
+            let b = std::slice::from_raw_parts(addr.data as *const u8, 16);
+            let mut octets = [0u8; 16];
+            octets.copy_from_slice(b);
+            Some(IpAddr::from(octets))
+        }
+        _ => None,
+    }
+}
+
+/// Recursively searches `tree` and its children for a field with header-field index `target_hf`,
+/// returning its string value.
+///
+/// # Safety
+/// `tree` must be a valid `proto_tree` built by the current packet's dissection (or null), as
+/// handed to a postdissector by `epan`.
+unsafe fn find_field_string(tree: *const proto_tree, target_hf: c_int) -> Option<String> {
+    if tree.is_null() || target_hf < 0 {
+        return None;
+    }
+    let node = &*tree;
+    if let Some(finfo) = node.finfo.as_mut() {
+        if !finfo.hfinfo.is_null() && (*finfo.hfinfo).id == target_hf {
+            let s = fvalue_get_string(&mut finfo.value);
+            if !s.is_null() {
+                return Some(CStr::from_ptr(s).to_string_lossy().into_owned());
+            }
+        }
+    }
+
+    let mut child = node.first_child;
+    while !child.is_null() {
+        if let Some(found) = find_field_string(child, target_hf) {
+            return Some(found);
+        }
+        child = (*child).next;
+    }
+    None
+}
+
+/// Checks the addresses and domain-shaped indicators matchy knows how to pull out of a packet
+/// against `database`, in the order a network operator would trust them: the resolved IP
+/// addresses first, then whatever hostname indicators the earlier dissectors exposed. Returns the
+/// match together with the name of the indicator that fired, for the `matchy.indicator` field.
+unsafe fn lookup_indicators(
+    database: &Database,
+    pinfo: &packet_info,
+    tree: *const proto_tree,
+) -> Option<(&'static str, Match)> {
+    [address_to_ip(&pinfo.src), address_to_ip(&pinfo.dst)]
+        .into_iter()
+        .flatten()
+        .find_map(|ip| database.lookup_ip(ip).map(|m| ("ip", m)))
+        .or_else(|| {
+            [
+                (TARGET_HF_DNS_QRY_NAME, "dns"),
+                (TARGET_HF_TLS_SNI, "tls_sni"),
+                (TARGET_HF_HTTP_HOST, "http_host"),
+            ]
+            .into_iter()
+            .find_map(|(hf, name)| {
+                let value = find_field_string(tree, hf)?;
+                database.lookup_domain(&value).map(|m| (name, m))
+            })
+        })
+}
+
+/// Reloads the database from `matchy.database_path`. Registered as the preferences `apply_cb`, so
+/// it runs at startup and whenever the user changes the path.
+unsafe extern "C" fn apply_prefs() {
+    if PREF_DATABASE_PATH.is_null() {
+        return;
+    }
+    let path = CStr::from_ptr(PREF_DATABASE_PATH)
+        .to_string_lossy()
+        .into_owned();
+    if path.is_empty() {
+        *DATABASE.lock().unwrap() = None;
+        return;
+    }
+    match Database::load(Path::new(&path)) {
+        Ok(db) => *DATABASE.lock().unwrap() = Some(db),
+        Err(e) => eprintln!("matchy: {e}"),
+    }
+}
+
+unsafe extern "C" fn dissect_matchy(
+    tvb: *mut tvbuff_t,
+    pinfo: *mut packet_info,
+    tree: *mut proto_tree,
+    _data: *mut c_void,
+) -> c_int {
+    let database = DATABASE.lock().unwrap();
+    let Some(database) = database.as_ref() else {
+        return 0;
+    };
+
+    let pinfo = &*pinfo;
+    let Some((indicator, hit)) = lookup_indicators(database, pinfo, tree) else {
+        return 0;
+    };
+
+    let item = proto_tree_add_boolean(tree, HF_THREAT_DETECTED, tvb, 0, 0, 1);
+    let subtree = proto_item_add_subtree(item, ETT_MATCHY);
+
+    let level = CString::new(hit.level.to_string()).unwrap();
+    let indicator = CString::new(indicator).unwrap();
+    // Unlike `level`/`indicator` above, `category` comes straight from the loaded `.mxy` file.
+    // `Database::parse` rejects an embedded NUL there at load time, but don't trust that from the
+    // packet-processing hot path too: fail this one packet's lookup instead of unwrapping.
+    let Ok(category) = CString::new(hit.category) else {
+        return 0;
+    };
+    proto_tree_add_string(subtree, HF_LEVEL, tvb, 0, 0, level.as_ptr());
+    proto_tree_add_string(subtree, HF_CATEGORY, tvb, 0, 0, category.as_ptr());
+    proto_tree_add_string(subtree, HF_INDICATOR, tvb, 0, 0, indicator.as_ptr());
+
+    0
+}
+
+fn hf_entry(
+    id: *mut c_int,
+    name: &'static CStr,
+    abbrev: &'static CStr,
+    field_type: epan_sys::ftenum,
+    blurb: &'static CStr,
+) -> hf_register_info {
+    hf_register_info {
+        p_id: id,
+        hfinfo: header_field_info {
+            name: name.as_ptr(),
+            abbrev: abbrev.as_ptr(),
+            type_: field_type,
+            display: field_display_e_BASE_NONE as c_int,
+            strings: std::ptr::null(),
+            bitmask: 0,
+            blurb: blurb.as_ptr(),
+            id: -1,
+            parent: -1,
+            ref_type: hf_ref_type_HF_REF_TYPE_NONE,
+            same_name_prev_id: -1,
+            same_name_next: std::ptr::null_mut(),
+        },
+    }
+}
+
+/// Registers the protocol, its fields, and its preferences with `epan`.
+///
+/// # Safety
+/// Must only be called by Wireshark's plugin loader, exactly once, before `proto_reg_handoff`
+/// and before any packet is dissected.
+#[no_mangle]
+pub unsafe extern "C" fn proto_register() {
+    PROTO_MATCHY = proto_register_protocol(
+        c"Matchy Threat Intelligence".as_ptr(),
+        c"Matchy".as_ptr(),
+        c"matchy".as_ptr(),
+    );
+
+    let hf = Box::leak(Box::new([
+        hf_entry(
+            std::ptr::addr_of_mut!(HF_THREAT_DETECTED),
+            c"Threat Detected",
+            c"matchy.threat_detected",
+            ftenum_FT_BOOLEAN,
+            c"Set when an address or hostname indicator matched an entry in the loaded matchy database",
+        ),
+        hf_entry(
+            std::ptr::addr_of_mut!(HF_LEVEL),
+            c"Threat Level",
+            c"matchy.level",
+            ftenum_FT_STRING,
+            c"Severity of the matched indicator",
+        ),
+        hf_entry(
+            std::ptr::addr_of_mut!(HF_CATEGORY),
+            c"Threat Category",
+            c"matchy.category",
+            ftenum_FT_STRING,
+            c"Category of the matched indicator",
+        ),
+        hf_entry(
+            std::ptr::addr_of_mut!(HF_INDICATOR),
+            c"Threat Indicator",
+            c"matchy.indicator",
+            ftenum_FT_STRING,
+            c"Which indicator fired: ip, dns, tls_sni, or http_host",
+        ),
+    ]));
+    proto_register_field_array(PROTO_MATCHY, hf.as_mut_ptr(), hf.len() as c_int);
+
+    let ett = Box::leak(Box::new([std::ptr::addr_of_mut!(ETT_MATCHY)]));
+    proto_register_subtree_array(ett.as_ptr(), ett.len() as c_int);
+
+    let module = prefs_register_protocol(PROTO_MATCHY, Some(apply_prefs));
+    prefs_register_filename_preference(
+        module,
+        c"database_path".as_ptr(),
+        c"Matchy database (.mxy) path".as_ptr(),
+        c"Path to the matchy threat-intelligence database this dissector matches traffic against."
+            .as_ptr(),
+        std::ptr::addr_of_mut!(PREF_DATABASE_PATH),
+        0,
+    );
+}
+
+/// Registers the postdissector with `epan` so it runs on every packet.
+///
+/// Also resolves the header-field indices of the other dissectors' indicators matchy matches
+/// domains against. This has to happen here rather than in `proto_register`: every dissector's
+/// `proto_register` callback runs before any dissector's `proto_reg_handoff`, so only by this
+/// point is `dns.qry.name` (and friends) guaranteed to already be in the field registry.
+///
+/// # Safety
+/// Must only be called by Wireshark's plugin loader, exactly once, after `proto_register`.
+#[no_mangle]
+pub unsafe extern "C" fn proto_reg_handoff() {
+    let handle = create_dissector_handle(Some(dissect_matchy), PROTO_MATCHY);
+    register_postdissector(handle);
+
+    TARGET_HF_DNS_QRY_NAME = proto_registrar_get_id_byname(c"dns.qry.name".as_ptr());
+    TARGET_HF_TLS_SNI =
+        proto_registrar_get_id_byname(c"tls.handshake.extensions_server_name".as_ptr());
+    TARGET_HF_HTTP_HOST = proto_registrar_get_id_byname(c"http.host".as_ptr());
+}
+
+static PLUGIN: proto_plugin = proto_plugin {
+    register_protoinfo: Some(proto_register),
+    register_handoff: Some(proto_reg_handoff),
+};
+
+#[no_mangle]
+pub extern "C" fn plugin_register() {
+    unsafe {
+        proto_register_plugin(&PLUGIN);
+    }
+}
@@ -0,0 +1,24 @@
+//! Wireshark postdissector that flags traffic matching a `matchy` threat-intelligence database.
+//!
+//! The dissector (in `dissector`, behind the default `wireshark-plugin` feature) runs after
+//! every protocol dissector has had a turn, reads whatever network-layer addresses `libwireshark`
+//! already resolved for the current packet (`pinfo->src`/`pinfo->dst`), and asks the loaded
+//! [`database::Database`] whether either address is listed. If neither address matches, it falls
+//! back to whatever domain-shaped indicators the earlier dissectors already pulled out of the
+//! packet — `dns.qry.name`, `tls.handshake.extensions_server_name`, and `http.host` — and checks
+//! those against [`database::Database::lookup_domain`]. Matches are surfaced as the
+//! `matchy.threat_detected`, `matchy.level`, `matchy.category`, and `matchy.indicator` fields that
+//! `tests/integration.rs` asserts on.
+//!
+//! The database itself is loaded from the path configured in the `matchy.database_path`
+//! preference, and reloaded whenever that preference changes.
+//!
+//! [`database`] and [`sensor`] don't depend on `epan-sys`/`libwireshark` and build with
+//! `--no-default-features`, so `matchy-sensor` and their own tests don't need a Wireshark
+//! toolchain.
+
+pub mod database;
+pub mod sensor;
+
+#[cfg(feature = "wireshark-plugin")]
+mod dissector;
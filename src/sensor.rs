@@ -0,0 +1,308 @@
+//! Standalone live-capture sensor.
+//!
+//! Unlike the Wireshark postdissector in `crate::dissector`, which only ever sees traffic that's
+//! already been saved to a `.pcap` and read back in by tshark, this module applies the matchy
+//! database directly to interfaces as packets arrive, so matchy can run as a deployable sensor
+//! rather than a passive Wireshark add-on. Unlike the dissector, it doesn't depend on
+//! `epan-sys`/`libwireshark` at all.
+//!
+//! A [`Listener`] owns one interface each; all listeners feed a single [`Sensor`], which matches
+//! every frame against the database, writes matches to a threats-only [`pcap::Savefile`], and
+//! surfaces them on a live [`PacketResult`] event stream.
+
+use std::net::IpAddr;
+use std::sync::Arc;
+
+use futures::StreamExt;
+use pcap::{Capture, Device, Packet, PacketCodec, PacketHeader, Savefile};
+use tokio::sync::mpsc;
+use tokio::task::JoinSet;
+
+use crate::database::{Database, Level};
+
+const ETHERNET_HEADER_LEN: usize = 14;
+const ETHERTYPE_IPV4: u16 = 0x0800;
+const ETHERTYPE_IPV6: u16 = 0x86dd;
+
+/// A frame read off an interface, decoded just enough to match against the database.
+pub struct CapturedFrame {
+    pub interface: String,
+    pub frame_number: u64,
+    pub src: Option<IpAddr>,
+    pub dst: Option<IpAddr>,
+    /// The capture timestamp `pcap` recorded for this frame, carried through so a threats-only
+    /// savefile reflects when traffic actually happened instead of when it was written out.
+    pub ts: libc::timeval,
+    pub data: Box<[u8]>,
+}
+
+/// A threat match on the live event stream: the frame and address it fired on, plus the same
+/// level/category fields the Wireshark dissector emits as `matchy.level`/`matchy.category`.
+pub struct PacketResult {
+    pub interface: String,
+    pub frame_number: u64,
+    pub src: Option<IpAddr>,
+    pub dst: Option<IpAddr>,
+    pub level: Level,
+    pub category: String,
+}
+
+struct FrameCodec;
+
+/// A frame's raw bytes plus the capture timestamp `pcap` stamped it with, as handed from
+/// [`FrameCodec::decode`] to [`Listener::run`].
+struct DecodedFrame {
+    ts: libc::timeval,
+    data: Box<[u8]>,
+}
+
+impl PacketCodec for FrameCodec {
+    type Item = DecodedFrame;
+
+    fn decode(&mut self, packet: Packet<'_>) -> Self::Item {
+        DecodedFrame {
+            ts: packet.header.ts,
+            data: packet.data.into(),
+        }
+    }
+}
+
+/// Pulls the source/destination IP out of a raw Ethernet frame, if it carries IPv4 or IPv6.
+/// Anything else (ARP, VLAN tags, non-IP payloads) is left unmatched rather than guessed at.
+fn parse_addresses(frame: &[u8]) -> (Option<IpAddr>, Option<IpAddr>) {
+    if frame.len() < ETHERNET_HEADER_LEN {
+        return (None, None);
+    }
+    let ethertype = u16::from_be_bytes([frame[12], frame[13]]);
+    let payload = &frame[ETHERNET_HEADER_LEN..];
+
+    match ethertype {
+        ETHERTYPE_IPV4 if payload.len() >= 20 => (
+            Some(IpAddr::from([
+                payload[12],
+                payload[13],
+                payload[14],
+                payload[15],
+            ])),
+            Some(IpAddr::from([
+                payload[16],
+                payload[17],
+                payload[18],
+                payload[19],
+            ])),
+        ),
+        ETHERTYPE_IPV6 if payload.len() >= 40 => {
+            let mut src = [0u8; 16];
+            let mut dst = [0u8; 16];
+            src.copy_from_slice(&payload[8..24]);
+            dst.copy_from_slice(&payload[24..40]);
+            (Some(IpAddr::from(src)), Some(IpAddr::from(dst)))
+        }
+        _ => (None, None),
+    }
+}
+
+/// Captures packets off a single named interface, forwarding each decoded frame to a shared
+/// channel. Keeping one `Listener` per interface (rather than one capture loop juggling several
+/// file descriptors) keeps each interface's blocking/nonblocking `pcap` state independent.
+pub struct Listener {
+    interface: String,
+}
+
+impl Listener {
+    pub fn new(interface: impl Into<String>) -> Listener {
+        Listener {
+            interface: interface.into(),
+        }
+    }
+
+    /// Opens the interface and streams frames into `tx` until the capture ends or the receiver
+    /// is dropped.
+    pub async fn run(self, tx: mpsc::Sender<CapturedFrame>) -> Result<(), pcap::Error> {
+        let capture = Capture::from_device(Device::from(self.interface.as_str()))?
+            .promisc(true)
+            .immediate_mode(true)
+            .open()?
+            .setnonblock()?;
+
+        let mut stream = capture.stream(FrameCodec)?;
+        let mut frame_number = 0u64;
+
+        while let Some(frame) = stream.next().await {
+            let frame = frame?;
+            frame_number += 1;
+            let (src, dst) = parse_addresses(&frame.data);
+
+            let frame = CapturedFrame {
+                interface: self.interface.clone(),
+                frame_number,
+                src,
+                dst,
+                ts: frame.ts,
+                data: frame.data,
+            };
+            if tx.send(frame).await.is_err() {
+                break; // consumer gone; nothing left to do
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Matches frames from one or more [`Listener`]s against a [`Database`], writing hits to a
+/// threats-only savefile and a live event stream.
+pub struct Sensor {
+    database: Arc<Database>,
+    threats_out: Savefile,
+}
+
+impl Sensor {
+    pub fn new(database: Database, threats_out: Savefile) -> Sensor {
+        Sensor {
+            database: Arc::new(database),
+            threats_out,
+        }
+    }
+
+    /// Spawns a [`Listener`] per interface and matches frames until all of them exit.
+    pub async fn run(
+        mut self,
+        interfaces: Vec<String>,
+        events: mpsc::Sender<PacketResult>,
+    ) -> Result<(), pcap::Error> {
+        let (frames_tx, mut frames_rx) = mpsc::channel(1024);
+
+        let mut listeners = JoinSet::new();
+        for interface in interfaces {
+            listeners.spawn(Listener::new(interface).run(frames_tx.clone()));
+        }
+        drop(frames_tx); // only the listeners should keep this channel alive
+
+        while let Some(frame) = frames_rx.recv().await {
+            let Some(hit) = self.lookup(&frame) else {
+                continue;
+            };
+
+            self.threats_out.write(&Packet::new(
+                &PacketHeader {
+                    ts: frame.ts,
+                    caplen: frame.data.len() as u32,
+                    len: frame.data.len() as u32,
+                },
+                &frame.data,
+            ));
+
+            let result = PacketResult {
+                interface: frame.interface,
+                frame_number: frame.frame_number,
+                src: frame.src,
+                dst: frame.dst,
+                level: hit.level,
+                category: hit.category,
+            };
+            if events.send(result).await.is_err() {
+                break;
+            }
+        }
+
+        while listeners.join_next().await.is_some() {}
+        Ok(())
+    }
+
+    fn lookup(&self, frame: &CapturedFrame) -> Option<crate::database::Match> {
+        frame
+            .src
+            .and_then(|ip| self.database.lookup_ip(ip))
+            .or_else(|| frame.dst.and_then(|ip| self.database.lookup_ip(ip)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An Ethernet/IPv4 frame with the given source/destination addresses. Only the header
+    /// fields `parse_addresses` reads are filled in; the rest are zeroed.
+    fn ethernet_ipv4_frame(src: [u8; 4], dst: [u8; 4]) -> Vec<u8> {
+        let mut frame = vec![0u8; ETHERNET_HEADER_LEN + 20];
+        frame[12..14].copy_from_slice(&ETHERTYPE_IPV4.to_be_bytes());
+        frame[ETHERNET_HEADER_LEN + 12..ETHERNET_HEADER_LEN + 16].copy_from_slice(&src);
+        frame[ETHERNET_HEADER_LEN + 16..ETHERNET_HEADER_LEN + 20].copy_from_slice(&dst);
+        frame
+    }
+
+    #[test]
+    fn parses_ipv4_addresses() {
+        let frame = ethernet_ipv4_frame([192, 168, 1, 1], [10, 0, 0, 1]);
+        let (src, dst) = parse_addresses(&frame);
+        assert_eq!(src, Some(IpAddr::from([192, 168, 1, 1])));
+        assert_eq!(dst, Some(IpAddr::from([10, 0, 0, 1])));
+    }
+
+    #[test]
+    fn non_ip_frame_has_no_addresses() {
+        let mut frame = vec![0u8; ETHERNET_HEADER_LEN + 20];
+        frame[12..14].copy_from_slice(&0x0806u16.to_be_bytes()); // ARP
+        let (src, dst) = parse_addresses(&frame);
+        assert_eq!((src, dst), (None, None));
+    }
+
+    #[test]
+    fn sensor_matches_either_address_against_the_database() {
+        let database = tempfile_database("192.168.1.1\tHigh\tmalware\n");
+        let threats_out = tempfile_savefile();
+        let sensor = Sensor::new(database, threats_out);
+
+        let frame = CapturedFrame {
+            interface: "eth0".to_string(),
+            frame_number: 1,
+            src: Some(IpAddr::from([10, 0, 0, 1])),
+            dst: Some(IpAddr::from([192, 168, 1, 1])),
+            ts: libc::timeval {
+                tv_sec: 0,
+                tv_usec: 0,
+            },
+            data: Box::new([]),
+        };
+        let hit = sensor.lookup(&frame).expect("destination should match");
+        assert_eq!(hit.category, "malware");
+
+        let clean = CapturedFrame {
+            interface: "eth0".to_string(),
+            frame_number: 2,
+            src: Some(IpAddr::from([8, 8, 8, 8])),
+            dst: Some(IpAddr::from([1, 1, 1, 1])),
+            ts: libc::timeval {
+                tv_sec: 0,
+                tv_usec: 0,
+            },
+            data: Box::new([]),
+        };
+        assert!(sensor.lookup(&clean).is_none());
+    }
+
+    fn tempfile_savefile() -> Savefile {
+        let path = std::env::temp_dir().join(format!(
+            "matchy-sensor-test-{}-{:?}.pcap",
+            std::process::id(),
+            std::thread::current().id()
+        ));
+        pcap::Capture::dead(pcap::Linktype::ETHERNET)
+            .and_then(|dead| dead.savefile(&path))
+            .expect("failed to open scratch savefile")
+    }
+
+    /// Writes `contents` to a scratch `.mxy` file and loads it, exercising the same public
+    /// `Database::load` path a real deployment would use.
+    fn tempfile_database(contents: &str) -> Database {
+        let path = std::env::temp_dir().join(format!(
+            "matchy-sensor-test-{}-{:?}.mxy",
+            std::process::id(),
+            std::thread::current().id()
+        ));
+        std::fs::write(&path, contents).expect("failed to write scratch database");
+        let database = Database::load(&path).expect("failed to load scratch database");
+        let _ = std::fs::remove_file(&path);
+        database
+    }
+}
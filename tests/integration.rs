@@ -9,7 +9,7 @@
 //!
 //! Run with: cargo test --test integration
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
 /// Get the path to the test fixtures directory
@@ -76,6 +76,8 @@ fn run_tshark_test() -> Result<Vec<PacketResult>, String> {
             "matchy.level",
             "-e",
             "matchy.category",
+            "-e",
+            "matchy.indicator",
         ])
         .output()
         .map_err(|e| format!("Failed to run tshark: {}", e))?;
@@ -98,6 +100,7 @@ struct PacketResult {
     threat_detected: bool,
     threat_level: Option<String>,
     category: Option<String>,
+    indicator: Option<String>,
 }
 
 /// Parse tab-separated tshark output into PacketResults
@@ -125,8 +128,18 @@ fn parse_tshark_output(output: &str) -> Result<Vec<PacketResult>, String> {
             src_ip: fields[1].to_string(),
             dst_ip: fields[2].to_string(),
             threat_detected,
-            threat_level: fields.get(4).filter(|s| !s.is_empty()).map(|s| s.to_string()),
-            category: fields.get(5).filter(|s| !s.is_empty()).map(|s| s.to_string()),
+            threat_level: fields
+                .get(4)
+                .filter(|s| !s.is_empty())
+                .map(|s| s.to_string()),
+            category: fields
+                .get(5)
+                .filter(|s| !s.is_empty())
+                .map(|s| s.to_string()),
+            indicator: fields
+                .get(6)
+                .filter(|s| !s.is_empty())
+                .map(|s| s.to_string()),
         });
     }
 
@@ -149,13 +162,16 @@ fn test_plugin_integration() {
 
     let results = run_tshark_test().expect("Failed to run tshark test");
 
-    assert_eq!(results.len(), 4, "Expected 4 packets in test pcap");
+    assert_eq!(results.len(), 5, "Expected 5 packets in test pcap");
 
     // Frame 1: dst=192.168.1.1 (exact match) -> high threat, malware
     let pkt1 = &results[0];
     assert_eq!(pkt1.frame_number, 1);
     assert_eq!(pkt1.dst_ip, "192.168.1.1");
-    assert!(pkt1.threat_detected, "Frame 1 should detect threat on dst IP");
+    assert!(
+        pkt1.threat_detected,
+        "Frame 1 should detect threat on dst IP"
+    );
     assert_eq!(
         pkt1.threat_level.as_deref(),
         Some("High"),
@@ -166,6 +182,7 @@ fn test_plugin_integration() {
         Some("malware"),
         "Frame 1 category"
     );
+    assert_eq!(pkt1.indicator.as_deref(), Some("ip"), "Frame 1 indicator");
 
     // Frame 2: dst=10.1.2.3 (matches 10.0.0.0/8 CIDR) -> medium threat, internal
     let pkt2 = &results[1];
@@ -185,6 +202,7 @@ fn test_plugin_integration() {
         Some("internal"),
         "Frame 2 category"
     );
+    assert_eq!(pkt2.indicator.as_deref(), Some("ip"), "Frame 2 indicator");
 
     // Frame 3: src=192.168.1.1 (threat as source) -> high threat, malware
     let pkt3 = &results[2];
@@ -204,6 +222,7 @@ fn test_plugin_integration() {
         Some("malware"),
         "Frame 3 category"
     );
+    assert_eq!(pkt3.indicator.as_deref(), Some("ip"), "Frame 3 indicator");
 
     // Frame 4: clean packet (8.8.8.8 -> 1.1.1.1) -> no threat
     let pkt4 = &results[3];
@@ -214,6 +233,144 @@ fn test_plugin_integration() {
     );
     assert!(pkt4.threat_level.is_none(), "Frame 4 should have no level");
     assert!(pkt4.category.is_none(), "Frame 4 should have no category");
+    assert!(pkt4.indicator.is_none(), "Frame 4 should have no indicator");
+
+    // Frame 5: clean IPs, but a TLS ClientHello SNI of c2.evil.example (a `.mxy` domain entry)
+    // -> high threat, malware, flagged on the SNI even though neither IP is listed
+    let pkt5 = &results[4];
+    assert_eq!(pkt5.frame_number, 5);
+    assert!(
+        pkt5.threat_detected,
+        "Frame 5 should detect threat via TLS SNI match"
+    );
+    assert_eq!(
+        pkt5.threat_level.as_deref(),
+        Some("High"),
+        "Frame 5 threat level"
+    );
+    assert_eq!(
+        pkt5.category.as_deref(),
+        Some("malware"),
+        "Frame 5 category"
+    );
+    assert_eq!(
+        pkt5.indicator.as_deref(),
+        Some("tls_sni"),
+        "Frame 5 indicator"
+    );
 
     eprintln!("All integration tests passed!");
 }
+
+/// Builds the plugin targeting a specific Wireshark major/minor ABI and drops it into
+/// `plugin_dir/<major>.<minor>/epan/`, the layout Wireshark expects under
+/// `WIRESHARK_PLUGIN_DIR`.
+fn build_plugin_for_abi(plugin_dir: &Path, ws_major: u32, ws_minor: u32) -> Result<(), String> {
+    let status = Command::new(env!("CARGO"))
+        .args(["build", "--lib"])
+        .env("PLUGIN_MAJOR_VERSION", ws_major.to_string())
+        .env("PLUGIN_MINOR_VERSION", ws_minor.to_string())
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .status()
+        .map_err(|e| format!("failed to run cargo build: {e}"))?;
+    if !status.success() {
+        return Err(format!(
+            "cargo build failed for Wireshark {ws_major}.{ws_minor}"
+        ));
+    }
+
+    let built = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("target")
+        .join("debug")
+        .join(format!(
+            "{}matchy_wireshark{}",
+            std::env::consts::DLL_PREFIX,
+            std::env::consts::DLL_SUFFIX
+        ));
+
+    let dest_dir = plugin_dir
+        .join(format!("{ws_major}.{ws_minor}"))
+        .join("epan");
+    std::fs::create_dir_all(&dest_dir)
+        .map_err(|e| format!("failed to create {}: {e}", dest_dir.display()))?;
+    let dest = dest_dir.join(format!("matchy{}", std::env::consts::DLL_SUFFIX));
+    std::fs::copy(&built, &dest).map_err(|e| {
+        format!(
+            "failed to copy {} to {}: {e}",
+            built.display(),
+            dest.display()
+        )
+    })?;
+
+    Ok(())
+}
+
+/// Checks whether the matchy plugin is reported by a tshark pointed at `plugin_dir` via
+/// `WIRESHARK_PLUGIN_DIR`, independent of anything installed system-wide.
+fn plugin_loaded_from(plugin_dir: &Path) -> bool {
+    Command::new("tshark")
+        .args(["-G", "plugins"])
+        .env("WIRESHARK_PLUGIN_DIR", plugin_dir)
+        .output()
+        .map(|o| {
+            let stdout = String::from_utf8_lossy(&o.stdout);
+            stdout.to_lowercase().contains("matchy")
+        })
+        .unwrap_or(false)
+}
+
+/// Parses `tshark --version`'s leading `TShark (Wireshark) X.Y.Z` line into `(X, Y)`, the ABI a
+/// locally installed tshark actually expects. A plugin's stamped `plugin_want_major`/
+/// `plugin_want_minor` must match this exactly or the loader rejects it outright.
+fn installed_tshark_version() -> Option<(u32, u32)> {
+    let output = Command::new("tshark").arg("--version").output().ok()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let version = stdout.split_whitespace().find(|s| {
+        s.chars()
+            .next()
+            .map(|c| c.is_ascii_digit())
+            .unwrap_or(false)
+    })?;
+    let mut parts = version.split('.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next()?.parse().ok()?;
+    Some((major, minor))
+}
+
+/// Verifies Wireshark's plugin loader enforces the ABI stamp `build.rs` writes: a plugin built
+/// for the installed tshark's own major.minor registers, and one deliberately built for a
+/// different minor version does not.
+#[test]
+fn test_plugin_registers_across_wireshark_abi_versions() {
+    if !tshark_available() {
+        eprintln!("SKIP: tshark not found in PATH");
+        return;
+    }
+    let Some((ws_major, ws_minor)) = installed_tshark_version() else {
+        eprintln!("SKIP: could not determine installed tshark version");
+        return;
+    };
+
+    let tmp = std::env::temp_dir().join(format!("matchy-abi-matrix-{}", std::process::id()));
+
+    let matching_dir = tmp.join(format!("{ws_major}.{ws_minor}-dir"));
+    build_plugin_for_abi(&matching_dir, ws_major, ws_minor)
+        .unwrap_or_else(|e| panic!("building for installed Wireshark {ws_major}.{ws_minor}: {e}"));
+    assert!(
+        plugin_loaded_from(&matching_dir),
+        "plugin built for the installed Wireshark's own ABI ({ws_major}.{ws_minor}) did not register"
+    );
+
+    let mismatched_minor = ws_minor + 1;
+    let mismatched_dir = tmp.join(format!("{ws_major}.{mismatched_minor}-dir"));
+    build_plugin_for_abi(&mismatched_dir, ws_major, mismatched_minor).unwrap_or_else(|e| {
+        panic!("building for mismatched Wireshark {ws_major}.{mismatched_minor}: {e}")
+    });
+    assert!(
+        !plugin_loaded_from(&mismatched_dir),
+        "plugin built for Wireshark {ws_major}.{mismatched_minor} registered against a \
+         {ws_major}.{ws_minor} tshark, but the ABI versions don't match"
+    );
+
+    let _ = std::fs::remove_dir_all(&tmp);
+}